@@ -0,0 +1,242 @@
+use arbitrary::Unstructured;
+use scupt_util::message::Message;
+use scupt_util::node_id::NID;
+use serde_json::Value;
+
+use crate::fuzzy_event::FuzzyEvent;
+use crate::fuzzy_setting::FuzzySetting;
+
+/// Draws fault decisions for each incoming message from an `Unstructured`
+/// byte stream so a run can be replayed deterministically from the same bytes.
+#[derive(Clone)]
+pub struct EventGen {
+    nodes: Vec<NID>,
+    setting: FuzzySetting,
+}
+
+impl EventGen {
+    pub fn new(nodes: Vec<NID>, setting: FuzzySetting) -> Self {
+        Self { nodes, setting }
+    }
+
+    /// Fuzzes a single incoming message, appending zero or more `FuzzyEvent`s
+    /// to `out`. Returns `false` when the `Unstructured` input is exhausted,
+    /// signalling the caller to stop the run.
+    pub fn fuzz_message(
+        &self,
+        message: &Message<String>,
+        u: &mut Unstructured,
+        out: &mut Vec<FuzzyEvent>,
+    ) -> bool {
+        if u.is_empty() {
+            return false;
+        }
+        let choice: u8 = u.arbitrary().unwrap_or(0);
+        match choice % 7 {
+            0 => {
+                let ms: u64 = u.int_in_range(0..=self.setting.max_delay_ms).unwrap_or(0);
+                out.push(FuzzyEvent::Delay(ms, message.clone()));
+            }
+            1 => {
+                let count: usize = u.int_in_range(1..=self.setting.max_duplicate).unwrap_or(1);
+                let delays = (0..count)
+                    .map(|_| u.int_in_range(0..=self.setting.max_delay_ms).unwrap_or(0))
+                    .collect();
+                out.push(FuzzyEvent::Duplicate(delays, message.clone()));
+            }
+            2 => {
+                out.push(FuzzyEvent::Lost);
+            }
+            3 if self.nodes.len() >= 2 && self.should_partition(u) => {
+                let (ids1, ids2) = self.split_nodes(u);
+                out.push(FuzzyEvent::PartitionStart(ids1, ids2));
+            }
+            4 => {
+                out.push(FuzzyEvent::Mutate(self.mutate_message(message, u)));
+            }
+            _ => {
+                out.push(FuzzyEvent::Delay(0, message.clone()));
+            }
+        }
+        true
+    }
+
+    /// Rolls against `setting.partition_percent` to decide whether this is
+    /// one of the fuzzed decisions that starts a partition, rather than
+    /// always partitioning whenever the 1-in-7 choice lands on it.
+    fn should_partition(&self, u: &mut Unstructured) -> bool {
+        let roll: u8 = u.int_in_range(0..=99).unwrap_or(100);
+        roll < self.setting.partition_percent
+    }
+
+    fn split_nodes(&self, u: &mut Unstructured) -> (Vec<NID>, Vec<NID>) {
+        let mut ids1 = vec![];
+        let mut ids2 = vec![];
+        for id in &self.nodes {
+            let side: bool = u.arbitrary().unwrap_or(false);
+            if side {
+                ids1.push(*id);
+            } else {
+                ids2.push(*id);
+            }
+        }
+        (ids1, ids2)
+    }
+
+    /// Corrupts `message`'s JSON payload, mostly via a structured perturbation
+    /// (flip a bool, bump/zero a number, truncate/duplicate an array element,
+    /// drop a field) and occasionally (1 in 10) via a raw bit flip on the
+    /// serialized bytes. All randomness is drawn from `u` so mutations are
+    /// reproducible and participate in replay.
+    fn mutate_message(&self, message: &Message<String>, u: &mut Unstructured) -> Message<String> {
+        let payload = message.payload();
+        let use_byte_flip = u.int_in_range(0..=9u8).unwrap_or(1) == 0;
+        let mutated = if use_byte_flip {
+            Self::mutate_bytes(payload, u)
+        } else {
+            match serde_json::from_str::<Value>(payload) {
+                Ok(mut value) => {
+                    if Self::perturb_json(&mut value, u) {
+                        serde_json::to_string(&value).unwrap_or_else(|_| payload.clone())
+                    } else {
+                        Self::mutate_bytes(payload, u)
+                    }
+                }
+                Err(_) => Self::mutate_bytes(payload, u),
+            }
+        };
+        message.clone().map(|_| mutated)
+    }
+
+    fn mutate_bytes(payload: &str, u: &mut Unstructured) -> String {
+        let mut bytes = payload.as_bytes().to_vec();
+        if bytes.is_empty() {
+            return payload.to_string();
+        }
+        let idx: usize = u.int_in_range(0..=bytes.len() - 1).unwrap_or(0);
+        let bit: u8 = u.int_in_range(0..=7u8).unwrap_or(0);
+        bytes[idx] ^= 1 << bit;
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Mutates one field of a JSON object in place. Returns `false` if there
+    /// was no object field to perturb, so the caller can fall back to a byte flip.
+    fn perturb_json(value: &mut Value, u: &mut Unstructured) -> bool {
+        let Value::Object(map) = value else {
+            return false;
+        };
+        let keys: Vec<String> = map.keys().cloned().collect();
+        if keys.is_empty() {
+            return false;
+        }
+        let idx: usize = u.int_in_range(0..=keys.len() - 1).unwrap_or(0);
+        let key = &keys[idx];
+        match map.get_mut(key) {
+            Some(Value::Bool(b)) => {
+                *b = !*b;
+            }
+            Some(Value::Number(n)) => {
+                let zero: bool = u.arbitrary().unwrap_or(false);
+                let mutated = if zero {
+                    0i64
+                } else {
+                    n.as_i64().map(|v| v.wrapping_add(1)).unwrap_or(0)
+                };
+                *n = serde_json::Number::from(mutated);
+            }
+            Some(Value::Array(arr)) if !arr.is_empty() => {
+                if u.arbitrary().unwrap_or(false) {
+                    let last = arr.last().unwrap().clone();
+                    arr.push(last);
+                } else {
+                    arr.pop();
+                }
+            }
+            _ => {
+                map.remove(key);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perturb_json_flips_bool_field() {
+        let mut value = serde_json::json!({"flag": true});
+        let mut u = Unstructured::new(&[0; 8]);
+        assert!(EventGen::perturb_json(&mut value, &mut u));
+        assert_eq!(value, serde_json::json!({"flag": false}));
+    }
+
+    #[test]
+    fn perturb_json_bumps_number_field_on_the_non_zero_choice() {
+        let mut value = serde_json::json!({"count": 5});
+        let mut u = Unstructured::new(&[0; 8]);
+        assert!(EventGen::perturb_json(&mut value, &mut u));
+        assert_eq!(value, serde_json::json!({"count": 6}));
+    }
+
+    #[test]
+    fn perturb_json_zeroes_number_field_on_the_zero_choice() {
+        let mut value = serde_json::json!({"count": 5});
+        let mut u = Unstructured::new(&[1; 8]);
+        assert!(EventGen::perturb_json(&mut value, &mut u));
+        assert_eq!(value, serde_json::json!({"count": 0}));
+    }
+
+    #[test]
+    fn perturb_json_duplicates_last_array_element_on_the_duplicate_choice() {
+        let mut value = serde_json::json!({"items": [1, 2]});
+        let mut u = Unstructured::new(&[1; 8]);
+        assert!(EventGen::perturb_json(&mut value, &mut u));
+        assert_eq!(value, serde_json::json!({"items": [1, 2, 2]}));
+    }
+
+    #[test]
+    fn perturb_json_truncates_array_on_the_pop_choice() {
+        let mut value = serde_json::json!({"items": [1, 2]});
+        let mut u = Unstructured::new(&[0; 8]);
+        assert!(EventGen::perturb_json(&mut value, &mut u));
+        assert_eq!(value, serde_json::json!({"items": [1]}));
+    }
+
+    #[test]
+    fn perturb_json_drops_a_field_of_an_unhandled_kind() {
+        let mut value = serde_json::json!({"name": "node-1"});
+        let mut u = Unstructured::new(&[0; 8]);
+        assert!(EventGen::perturb_json(&mut value, &mut u));
+        assert_eq!(value, serde_json::json!({}));
+    }
+
+    #[test]
+    fn perturb_json_returns_false_for_a_non_object() {
+        let mut value = serde_json::json!([1, 2, 3]);
+        let mut u = Unstructured::new(&[0; 8]);
+        assert!(!EventGen::perturb_json(&mut value, &mut u));
+    }
+
+    #[test]
+    fn mutate_bytes_flips_exactly_one_bit() {
+        let payload = "abcdefgh";
+        let mut u = Unstructured::new(&[3, 5, 9, 1]);
+        let mutated = EventGen::mutate_bytes(payload, &mut u);
+        assert_eq!(mutated.len(), payload.len());
+        let diff_bits: u32 = payload
+            .as_bytes()
+            .iter()
+            .zip(mutated.as_bytes().iter())
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum();
+        assert_eq!(diff_bits, 1, "expected exactly one bit flipped, got {mutated:?} vs {payload:?}");
+    }
+
+    #[test]
+    fn mutate_bytes_is_a_no_op_on_empty_payload() {
+        let mut u = Unstructured::new(&[0, 0]);
+        assert_eq!(EventGen::mutate_bytes("", &mut u), "");
+    }
+}
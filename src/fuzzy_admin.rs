@@ -0,0 +1,60 @@
+use scupt_util::node_id::NID;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// A fault to apply to the very next message sent between a specific pair of
+/// nodes, set via the admin API rather than drawn from the fuzz byte stream.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ForcedFault {
+    Drop,
+    Delay(u64),
+}
+
+/// Snapshot of run-wide counters, returned in response to `QueryStats`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FuzzyStats {
+    pub total_actions: u64,
+    pub total_deliveries: u64,
+    pub partitioned_pairs: Vec<(NID, NID)>,
+}
+
+/// Commands a test harness can send at runtime to drive `FuzzyInner` directly,
+/// instead of only through the pre-seeded fuzz byte stream.
+///
+/// This is an in-process control surface: a command enum consumed through
+/// the same `Arc<dyn ReceiverAsync<_>>` abstraction `FuzzyCommand` already
+/// uses for fuzzed messages (`FuzzyDriver::admin_loop`), so a harness in the
+/// same process/test binary drives it by holding a `FuzzyDriver` handle (or
+/// the sender half of its receiver). For a reproduction script or harness
+/// running *outside* this process, see `fuzzy_admin_server`, which exposes
+/// the same set of operations as `AdminRequest`/`AdminResponse` over a plain
+/// TCP/JSON-lines listener.
+pub enum FuzzyAdminCommand {
+    PartitionStart(Vec<NID>, Vec<NID>),
+    PartitionEnd(Vec<NID>, Vec<NID>),
+    ForceNext(NID, NID, ForcedFault),
+    SetFaultInjectionEnabled(bool),
+    QueryStats(oneshot::Sender<FuzzyStats>),
+}
+
+/// Wire form of an admin command, read as one JSON object per line by
+/// `fuzzy_admin_server::serve_admin`. Mirrors `FuzzyAdminCommand` except for
+/// `QueryStats`, which carries no `oneshot::Sender` over the wire — the
+/// server answers it in-band with an `AdminResponse::Stats` on the same
+/// connection instead.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum AdminRequest {
+    PartitionStart(Vec<NID>, Vec<NID>),
+    PartitionEnd(Vec<NID>, Vec<NID>),
+    ForceNext(NID, NID, ForcedFault),
+    SetFaultInjectionEnabled(bool),
+    QueryStats,
+}
+
+/// Reply written back as one JSON object per line by `fuzzy_admin_server::serve_admin`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum AdminResponse {
+    Ack,
+    Stats(FuzzyStats),
+    Error(String),
+}
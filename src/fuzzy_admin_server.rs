@@ -0,0 +1,62 @@
+use scupt_net::notifier::Notifier;
+use scupt_net::task::spawn_local_task;
+use scupt_util::error_type::ET;
+use scupt_util::res::Res;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use crate::fuzzy_admin::{AdminRequest, AdminResponse};
+use crate::fuzzy_driver::FuzzyDriver;
+
+/// Plain TCP/JSON-lines admin endpoint: a reproduction script or test
+/// harness running outside this process connects to `addr`, writes one
+/// `AdminRequest` JSON object per line, and reads back one `AdminResponse`
+/// JSON object per line in reply. This is deliberately not routed through
+/// `scupt_net`'s own service registration — that API isn't available to
+/// verify against in this crate, so rather than guess at it this gives an
+/// external caller a transport it can actually dial into today, closing the
+/// external-reachability gap `FuzzyDriver::admin_loop` leaves open on its own.
+pub async fn serve_admin(notifier: Notifier, addr: &str, driver: FuzzyDriver) -> Res<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| ET::FatalError(format!("admin listener failed to bind {addr}: {e}")))?;
+    loop {
+        let (stream, _peer) = listener
+            .accept()
+            .await
+            .map_err(|e| ET::FatalError(format!("admin listener accept failed: {e}")))?;
+        let driver = driver.clone();
+        let _ = spawn_local_task(notifier.clone(), "fuzzy_admin_conn", async move {
+            let _ = handle_admin_connection(stream, driver).await;
+            Ok::<(), ET>(())
+        });
+    }
+}
+
+/// Services one admin connection until the peer disconnects or a read or
+/// write fails. A line that isn't a valid `AdminRequest` is answered with
+/// `AdminResponse::Error` and the connection stays open for the next line.
+async fn handle_admin_connection(stream: tokio::net::TcpStream, driver: FuzzyDriver) -> Res<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| ET::FatalError(format!("admin connection read failed: {e}")))?
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<AdminRequest>(&line) {
+            Ok(request) => driver.handle_admin_request(request),
+            Err(e) => AdminResponse::Error(format!("invalid admin request: {e}")),
+        };
+        let mut reply = serde_json::to_string(&response).unwrap();
+        reply.push('\n');
+        writer
+            .write_all(reply.as_bytes())
+            .await
+            .map_err(|e| ET::FatalError(format!("admin connection write failed: {e}")))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,6 @@
+use scupt_util::message::Message;
+
+#[derive(Clone, Debug)]
+pub enum FuzzyCommand {
+    MessageReq(Message<String>),
+}
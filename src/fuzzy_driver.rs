@@ -1,9 +1,10 @@
 use std::collections::HashSet;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Duration;
 use arbitrary::Unstructured;
 use rusqlite::Connection;
+use scc::HashMap as ConcurrentHashMap;
 use scc::HashSet as ConcurrentHashSet;
 use scupt_net::message_receiver_async::ReceiverAsync;
 use scupt_net::message_sender_async::SenderAsync;
@@ -16,11 +17,14 @@ use scupt_util::node_id::NID;
 use scupt_util::res::Res;
 use scupt_util::res_of::res_sqlite;
 use scupt_util::serde_json_string::SerdeJsonString;
-use tokio::time::sleep;
 
+use crate::fuzzy_admin::{AdminRequest, AdminResponse, FuzzyAdminCommand, FuzzyStats, ForcedFault};
 use crate::fuzzy_command::FuzzyCommand;
 use crate::fuzzy_event::FuzzyEvent;
 use crate::event_gen::EventGen;
+use crate::fuzzy_monitor::{FuzzyEventReceiver, FuzzyMonitor, FuzzyTopic};
+use crate::fuzzy_replay;
+use crate::fuzzy_scheduler::{FuzzyScheduler, ScheduledAction};
 use crate::fuzzy_setting::FuzzySetting;
 
 #[derive(Clone)]
@@ -35,7 +39,14 @@ struct FuzzyInner {
     dis_connect: ConcurrentHashSet<(NID, NID)>,
     atomic_sequence: AtomicU64,
     sender: Arc<dyn SenderAsync<SerdeJsonString>>,
-    path: String,
+    write_path: Mutex<String>,
+    monitor: FuzzyMonitor,
+    scheduler: FuzzyScheduler,
+    max_inflight_sends: usize,
+    fault_injection_enabled: AtomicBool,
+    forced_next: ConcurrentHashMap<(NID, NID), ForcedFault>,
+    total_actions: AtomicU64,
+    total_deliveries: AtomicU64,
 }
 
 impl FuzzyDriver {
@@ -45,36 +56,35 @@ impl FuzzyDriver {
         node_set: HashSet<NID>,
         setting:FuzzySetting,
         sender: Arc<dyn SenderAsync<SerdeJsonString>> ) -> Self {
+        let path_store = path.clone();
+        let inner = Arc::new(FuzzyInner {
+            dis_connect: Default::default(),
+            atomic_sequence: AtomicU64::new(0),
+            sender,
+            write_path: Mutex::new(path),
+            monitor: FuzzyMonitor::new(),
+            scheduler: FuzzyScheduler::new(),
+            max_inflight_sends: setting.max_inflight_sends,
+            fault_injection_enabled: AtomicBool::new(true),
+            forced_next: ConcurrentHashMap::new(),
+            total_actions: AtomicU64::new(0),
+            total_deliveries: AtomicU64::new(0),
+        });
+        let worker = inner.clone();
+        let _ = spawn_local_task(notifier.clone(), "fuzzy_scheduler", async move {
+            worker.run_scheduler().await;
+            Ok::<(), ET>(())
+        });
         Self {
-            path_store: path.clone(),
+            path_store,
             notifier,
-            inner: Arc::new(FuzzyInner {
-                dis_connect: Default::default(),
-                atomic_sequence: AtomicU64::new(0),
-                sender,
-                path,
-            }),
+            inner,
             event_gen: EventGen::new(node_set.iter().cloned().collect(), setting),
         }
     }
 
     pub fn create_db(&self) -> Res<()> {
-        let mut conn = Connection::open(self.path_store.clone()).unwrap();
-        let trans = res_sqlite(conn.transaction())?;
-        let _r = trans.execute(
-            r#"create table action (
-                    id interger primary key,
-                    event text not null
-                );"#, ());
-        res_sqlite(_r)?;
-        let _r = trans.execute(
-            r#"create table delivery (
-                    id interger primary key,
-                    action_id integer not null
-                );"#, ());
-        res_sqlite(_r)?;
-        trans.commit().unwrap();
-        Ok(())
+        create_schema(&self.path_store)
     }
 
 
@@ -90,15 +100,82 @@ impl FuzzyDriver {
         }
     }
 
+    /// Deterministic replay mode: instead of fuzzing fresh decisions from
+    /// random bytes, re-reads the `action` table
+    /// of a previously recorded run and re-injects the exact same events, so a
+    /// failing sequence can be reproduced and shrunk.
+    ///
+    /// Deliveries produced by the replay are written to `output_path`, a
+    /// fresh db created for this purpose, rather than back into `path_store`:
+    /// `path_store` is the original recording being read from, and a replay
+    /// that re-delivers the same messages must not mutate the very history
+    /// it's replaying.
+    pub async fn replay(&self, receiver: Arc<dyn ReceiverAsync<FuzzyCommand>>, output_path: String) -> Res<()> {
+        let recorded = fuzzy_replay::load_recorded_events(&self.path_store)?;
+        let mut recorded = recorded.into_iter();
+        create_schema(&output_path)?;
+        *self.inner.write_path.lock().unwrap() = output_path;
+        loop {
+            let msg = receiver.receive().await?;
+            self.incoming_command_replay(msg.payload(), &mut recorded).await?;
+        }
+    }
+
+    async fn incoming_command_replay(
+        &self,
+        command: FuzzyCommand,
+        recorded: &mut impl Iterator<Item = (u64, FuzzyEvent)>,
+    ) -> Res<()> {
+        match command {
+            FuzzyCommand::MessageReq(m) => {
+                let (id, event) = recorded.next().ok_or(ET::EOF)?;
+                self.check_replay_divergence(&m, &event)?;
+                self.schedule_fuzzy_event(id, event);
+            }
+        }
+        Ok(())
+    }
+
+    /// Confirms the live message stream matches the recorded sequence, since
+    /// a mismatch here means the run is no longer deterministic and replaying
+    /// further would just chase a different bug than the one recorded.
+    ///
+    /// Compares the payload too, not just the endpoints: the same node pair
+    /// exchanges many distinct messages in any real protocol, so an
+    /// endpoints-only check would pass even if replay substituted a
+    /// different message for the same pair. `Mutate` is the one exception —
+    /// its stored message is, by design, already the mutated payload
+    /// (see `FuzzyEvent::Mutate`'s doc comment), not the original live one,
+    /// so comparing payloads there would flag every mutation as a divergence.
+    fn check_replay_divergence(&self, live: &Message<String>, recorded: &FuzzyEvent) -> Res<()> {
+        if let Some(recorded_msg) = recorded.source_message() {
+            let endpoints_match = recorded_msg.source() == live.source() && recorded_msg.dest() == live.dest();
+            let payload_matches = matches!(recorded, FuzzyEvent::Mutate(_)) || recorded_msg.payload() == live.payload();
+            if !endpoints_match || !payload_matches {
+                return Err(ET::FatalError(format!(
+                    "replay divergence: recorded message {}->{} (payload {:?}) but live message is {}->{} (payload {:?})",
+                    recorded_msg.source(), recorded_msg.dest(), recorded_msg.payload(),
+                    live.source(), live.dest(), live.payload(),
+                )));
+            }
+        }
+        Ok(())
+    }
+
     pub async fn incoming_command(&self, command: FuzzyCommand, unstructured: &mut Unstructured<'_>) -> Res<()> {
         match command {
             FuzzyCommand::MessageReq(m) => {
+                if !self.inner.fault_injection_enabled.load(Ordering::SeqCst) {
+                    let id = self.inner.gen_id();
+                    self.fuzzy_event_for_message(id, FuzzyEvent::Delay(0, m)).await?;
+                    return Ok(());
+                }
                 let mut vec = vec![];
                 let cont = self.event_gen.fuzz_message(&m, unstructured, &mut vec);
 
                 for event in vec {
                     let id = self.inner.gen_id();
-                    self.fuzzy_event_for_message(id, event).await?;
+                    self.fuzzy_mutated_event_for_message(id, event, &m).await?;
                 }
                 if !cont {
                     return Err(ET::EOF);
@@ -108,71 +185,243 @@ impl FuzzyDriver {
         Ok(())
     }
 
-    fn store_event_message(&self, id: u64, event: FuzzyEvent) {
+    /// Runs the admin/control loop, letting a test harness drive `FuzzyInner`
+    /// at runtime (partitions, forced single-shot faults, stats) instead of
+    /// only through the pre-seeded fuzz byte stream. In-process only: see
+    /// `fuzzy_admin_server` for a version of this reachable from outside the
+    /// process.
+    pub async fn admin_loop(&self, receiver: Arc<dyn ReceiverAsync<FuzzyAdminCommand>>) -> Res<()> {
+        loop {
+            let msg = receiver.receive().await?;
+            self.handle_admin_command(msg.payload());
+        }
+    }
+
+    /// Handles one `AdminRequest` received over `fuzzy_admin_server`'s network
+    /// listener. Applies the same `FuzzyInner` mutations as
+    /// `handle_admin_command`, but returns the reply in-band rather than
+    /// through a `oneshot::Sender`, since a network connection has no Rust
+    /// channel to reply on.
+    pub fn handle_admin_request(&self, request: AdminRequest) -> AdminResponse {
+        match request {
+            AdminRequest::PartitionStart(ids1, ids2) => {
+                self.inner.partition_start(ids1, ids2);
+                AdminResponse::Ack
+            }
+            AdminRequest::PartitionEnd(ids1, ids2) => {
+                self.inner.partition_end(ids1, ids2);
+                AdminResponse::Ack
+            }
+            AdminRequest::ForceNext(src, dst, fault) => {
+                let _ = self.inner.forced_next.remove(&(src, dst));
+                let _ = self.inner.forced_next.insert((src, dst), fault);
+                AdminResponse::Ack
+            }
+            AdminRequest::SetFaultInjectionEnabled(enabled) => {
+                self.inner.fault_injection_enabled.store(enabled, Ordering::SeqCst);
+                AdminResponse::Ack
+            }
+            AdminRequest::QueryStats => AdminResponse::Stats(self.inner.stats()),
+        }
+    }
+
+    fn handle_admin_command(&self, command: FuzzyAdminCommand) {
+        match command {
+            FuzzyAdminCommand::PartitionStart(ids1, ids2) => {
+                self.inner.partition_start(ids1, ids2);
+            }
+            FuzzyAdminCommand::PartitionEnd(ids1, ids2) => {
+                self.inner.partition_end(ids1, ids2);
+            }
+            FuzzyAdminCommand::ForceNext(src, dst, fault) => {
+                let _ = self.inner.forced_next.remove(&(src, dst));
+                let _ = self.inner.forced_next.insert((src, dst), fault);
+            }
+            FuzzyAdminCommand::SetFaultInjectionEnabled(enabled) => {
+                self.inner.fault_injection_enabled.store(enabled, Ordering::SeqCst);
+            }
+            FuzzyAdminCommand::QueryStats(reply) => {
+                let _ = reply.send(self.inner.stats());
+            }
+        }
+    }
+
+    fn store_event_message(&self, id: u64, event: FuzzyEvent, diff: Option<String>) {
         let mut conn = Connection::open(self.path_store.clone()).unwrap();
         let transaction = conn.transaction().unwrap();
         let event_s = serde_json::to_string_pretty(&event).unwrap();
         let _ = transaction.execute("\
-                            insert into action(id,  event) \
-                            values(?1, ?2)", (&id, &event_s)).unwrap();
+                            insert into action(id,  event, diff) \
+                            values(?1, ?2, ?3)", (&id, &event_s, &diff)).unwrap();
         transaction.commit().unwrap();
+        self.inner.total_actions.fetch_add(1, Ordering::SeqCst);
     }
 
     async fn fuzzy_event_for_message(&self, id: u64, event: FuzzyEvent) -> Res<()> {
-        self.store_event_message(id, event.clone());
-        self.schedule_fuzzy_event(id, event).await?;
+        self.store_event_message(id, event.clone(), None);
+        self.schedule_fuzzy_event(id, event);
         Ok(())
     }
 
-    async fn schedule_fuzzy_event(&self, id: u64, event: FuzzyEvent) -> Res<()> {
-        let inner = self.inner.clone();
-        let _ = spawn_local_task(self.notifier.clone(), "", async move {
-            inner.schedule(id, event).await?;
-            Ok::<(), ET>(())
-        })?;
+    /// Like `fuzzy_event_for_message`, but also records the original-vs-mutated
+    /// payload diff alongside the event, for `FuzzyEvent::Mutate`.
+    async fn fuzzy_mutated_event_for_message(&self, id: u64, event: FuzzyEvent, original: &Message<String>) -> Res<()> {
+        let diff = match &event {
+            FuzzyEvent::Mutate(mutated) => Some(format!("{} -> {}", original.payload(), mutated.payload())),
+            _ => None,
+        };
+        self.store_event_message(id, event.clone(), diff);
+        self.schedule_fuzzy_event(id, event);
         Ok(())
     }
+
+    /// Expands `event` into its deadline-ordered effects and pushes them onto
+    /// the shared scheduler heap instead of spawning a task that sleeps.
+    fn schedule_fuzzy_event(&self, id: u64, event: FuzzyEvent) {
+        self.inner.monitor.notify(FuzzyTopic::Message, id, event.clone());
+        for (delay, action) in expand_event(id, event) {
+            self.inner.scheduler.push(delay, action);
+        }
+    }
+
+    /// Subscribes to a topic's live fault events, replaying recent history first.
+    pub fn subscribe(&self, topic: FuzzyTopic) -> FuzzyEventReceiver {
+        self.inner.monitor.subscribe(topic)
+    }
+}
+
+/// Creates the `action`/`delivery` tables a recording (or a replay's
+/// separate output db, see `FuzzyDriver::replay`) is written to.
+fn create_schema(path: &str) -> Res<()> {
+    let mut conn = Connection::open(path).unwrap();
+    let trans = res_sqlite(conn.transaction())?;
+    let _r = trans.execute(
+        r#"create table action (
+                id interger primary key,
+                event text not null,
+                diff text
+            );"#, ());
+    res_sqlite(_r)?;
+    let _r = trans.execute(
+        r#"create table delivery (
+                id integer primary key,
+                action_id integer not null
+            );"#, ());
+    res_sqlite(_r)?;
+    trans.commit().unwrap();
+    Ok(())
+}
+
+/// Records that `action_id` delivered. `id` is left for SQLite to autoassign
+/// as the `delivery` table's rowid, independent of `action`'s own id
+/// sequence, so an insert into a table that already holds delivery rows can
+/// never collide with an existing primary key.
+fn insert_delivery_row(path: &str, action_id: u64) -> Res<()> {
+    let mut conn = Connection::open(path).unwrap();
+    let transaction = conn.transaction().unwrap();
+    let _ = transaction.execute(
+        r#"insert into delivery(action_id)
+               values(?1)"#, (&action_id,)).unwrap();
+    transaction.commit().unwrap();
+    Ok(())
+}
+
+/// Expands a single `FuzzyEvent` into `(relative delay, action)` pairs. A
+/// `Duplicate` expands to one send per entry at cumulative offsets, since the
+/// original behavior sleeps between sends rather than firing them all at once.
+/// Each `Send` carries the originating event (for `Duplicate`, a single-delay
+/// `Duplicate` standing in for that one copy) so the `Delivery` topic can
+/// report what actually produced the delivery.
+fn expand_event(id: u64, event: FuzzyEvent) -> Vec<(Duration, ScheduledAction)> {
+    match event {
+        FuzzyEvent::Delay(ms, message) => {
+            let origin = FuzzyEvent::Delay(ms, message.clone());
+            vec![(Duration::from_millis(ms), ScheduledAction::Send(id, message, origin))]
+        }
+        FuzzyEvent::Duplicate(delays, message) => {
+            let mut offset_ms = 0u64;
+            delays
+                .into_iter()
+                .map(|ms| {
+                    offset_ms += ms;
+                    let origin = FuzzyEvent::Duplicate(vec![ms], message.clone());
+                    (Duration::from_millis(offset_ms), ScheduledAction::Send(id, message.clone(), origin))
+                })
+                .collect()
+        }
+        FuzzyEvent::Lost => vec![(Duration::ZERO, ScheduledAction::Lost(id))],
+        FuzzyEvent::Restart(ms, message) => {
+            let origin = FuzzyEvent::Restart(ms, message.clone());
+            vec![(Duration::from_millis(ms), ScheduledAction::Send(id, message, origin))]
+        }
+        FuzzyEvent::Crash(message) => {
+            vec![(Duration::ZERO, ScheduledAction::Crash(id, message))]
+        }
+        FuzzyEvent::PartitionStart(ids1, ids2) => {
+            vec![(Duration::ZERO, ScheduledAction::PartitionStart(id, ids1, ids2))]
+        }
+        FuzzyEvent::PartitionRecovery(ms, ids1, ids2) => {
+            vec![(Duration::from_millis(ms), ScheduledAction::PartitionEnd(id, ids1, ids2))]
+        }
+        FuzzyEvent::Mutate(message) => {
+            let origin = FuzzyEvent::Mutate(message.clone());
+            vec![(Duration::ZERO, ScheduledAction::Send(id, message, origin))]
+        }
+    }
 }
 
 impl FuzzyInner {
-    async fn schedule(&self, id: u64, event: FuzzyEvent) -> Res<()> {
-        match event {
-            FuzzyEvent::Delay(ms, message) => {
-                if ms > 0 {
-                    sleep(Duration::from_millis(ms)).await;
+    /// Worker loop: pulls due actions off the scheduler heap and dispatches
+    /// them with bounded concurrency. Runs for the lifetime of the driver.
+    async fn run_scheduler(self: Arc<Self>) {
+        let max_inflight = self.max_inflight_sends;
+        self.scheduler
+            .run(max_inflight, |action| {
+                let inner = self.clone();
+                async move {
+                    let _ = inner.dispatch(action).await;
                 }
-                self.send(id, message).await?;
-            }
-            FuzzyEvent::Duplicate(vec, message) => {
-                for ms in vec {
-                    sleep(Duration::from_millis(ms)).await;
-                    self.send(id, message.clone()).await?;
+            })
+            .await;
+    }
+
+    async fn dispatch(&self, action: ScheduledAction) -> Res<()> {
+        match action {
+            ScheduledAction::Send(id, message, event) => {
+                match self.take_forced_fault(message.source(), message.dest()) {
+                    Some(ForcedFault::Drop) => {}
+                    Some(ForcedFault::Delay(ms)) => {
+                        self.scheduler.push(Duration::from_millis(ms), ScheduledAction::Send(id, message, event));
+                    }
+                    None => {
+                        self.send(id, message, event).await?;
+                    }
                 }
             }
-            FuzzyEvent::Lost => {}
-            FuzzyEvent::Restart(ms, message) => {
-                sleep(Duration::from_millis(ms)).await;
-                self.send(id, message).await?;
-            }
-            FuzzyEvent::Crash(message) => {
-                self.send(id, message).await?;
+            ScheduledAction::Lost(_id) => {}
+            ScheduledAction::Crash(id, message) => {
+                let event = FuzzyEvent::Crash(message.clone());
+                self.monitor.notify(FuzzyTopic::Crash, id, event.clone());
+                self.send(id, message, event).await?;
             }
-            FuzzyEvent::PartitionStart(ids1, ids2) => {
+            ScheduledAction::PartitionStart(id, ids1, ids2) => {
+                self.monitor.notify(FuzzyTopic::Partition, id, FuzzyEvent::PartitionStart(ids1.clone(), ids2.clone()));
                 self.partition_start(ids1, ids2);
             }
-            FuzzyEvent::PartitionRecovery(ms, ids1, ids2) => {
-                sleep(Duration::from_millis(ms)).await;
+            ScheduledAction::PartitionEnd(id, ids1, ids2) => {
+                self.monitor.notify(FuzzyTopic::Partition, id, FuzzyEvent::PartitionRecovery(0, ids1.clone(), ids2.clone()));
                 self.partition_end(ids1, ids2);
             }
         }
         Ok(())
     }
 
-    async fn send(&self, id: u64, message: Message<String>) -> Res<()> {
+    async fn send(&self, id: u64, message: Message<String>, event: FuzzyEvent) -> Res<()> {
         if !self.can_connect(message.source(), message.dest()) {
             return Ok(());
         }
         self.store_message_delivery(id);
+        self.monitor.notify(FuzzyTopic::Delivery, id, event);
         let m = message.map(|s| {
             SerdeJsonString::new(s)
         });
@@ -186,14 +435,25 @@ impl FuzzyInner {
     }
 
     fn store_message_delivery(&self, action_id: u64) {
-        let mut conn = Connection::open(self.path.clone()).unwrap();
-        let id = self.gen_id();
-        let transaction = conn.transaction().unwrap();
-        let _ = transaction.execute(
-            r#"insert into delivery(id, action_id)
-                   values(?1, ?2)"#, (&id, &action_id)).unwrap();
+        let path = self.write_path.lock().unwrap().clone();
+        let _ = insert_delivery_row(&path, action_id).unwrap();
+        self.total_deliveries.fetch_add(1, Ordering::SeqCst);
+    }
 
-        transaction.commit().unwrap();
+    /// Takes (and clears) a forced single-shot fault set via the admin API
+    /// for this ordered pair, if one is pending.
+    fn take_forced_fault(&self, source: NID, dest: NID) -> Option<ForcedFault> {
+        self.forced_next.remove(&(source, dest)).map(|(_, fault)| fault)
+    }
+
+    fn stats(&self) -> FuzzyStats {
+        let mut partitioned_pairs = vec![];
+        self.dis_connect.iter().for_each(|pair| partitioned_pairs.push(*pair));
+        FuzzyStats {
+            total_actions: self.total_actions.load(Ordering::SeqCst),
+            total_deliveries: self.total_deliveries.load(Ordering::SeqCst),
+            partitioned_pairs,
+        }
     }
 
     fn can_connect(&self, id1: NID, id2: NID) -> bool {
@@ -218,3 +478,27 @@ impl FuzzyInner {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `insert_delivery_row` must never assign an id that collides with a
+    /// delivery row already present in the table, rather than panicking on
+    /// the insert.
+    #[test]
+    fn insert_delivery_row_does_not_collide_with_existing_ids() {
+        let path = format!("/tmp/scupt_fuzzy_test_delivery_{}.db", std::process::id());
+        let _ = std::fs::remove_file(&path);
+        create_schema(&path).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        conn.execute("insert into delivery(id, action_id) values (0, 0)", ()).unwrap();
+
+        insert_delivery_row(&path, 1).unwrap();
+        insert_delivery_row(&path, 2).unwrap();
+
+        let count: i64 = conn.query_row("select count(*) from delivery", (), |r| r.get(0)).unwrap();
+        assert_eq!(count, 3);
+        let _ = std::fs::remove_file(&path);
+    }
+}
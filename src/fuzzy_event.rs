@@ -0,0 +1,45 @@
+use scupt_util::message::Message;
+use scupt_util::node_id::NID;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum FuzzyEvent {
+    Delay(u64, Message<String>),
+    Duplicate(Vec<u64>, Message<String>),
+    Lost,
+    Restart(u64, Message<String>),
+    Crash(Message<String>),
+    PartitionStart(Vec<NID>, Vec<NID>),
+    PartitionRecovery(u64, Vec<NID>, Vec<NID>),
+    /// Carries the already-mutated message so a Byzantine/corruption test can
+    /// exercise deserialization hardening on the receiving side.
+    Mutate(Message<String>),
+}
+
+impl FuzzyEvent {
+    /// The message this event was fuzzed from, if any. `Lost`, `PartitionStart`
+    /// and `PartitionRecovery` aren't derived from a single incoming message.
+    pub fn source_message(&self) -> Option<&Message<String>> {
+        match self {
+            FuzzyEvent::Delay(_, message)
+            | FuzzyEvent::Restart(_, message)
+            | FuzzyEvent::Crash(message)
+            | FuzzyEvent::Mutate(message) => Some(message),
+            FuzzyEvent::Duplicate(_, message) => Some(message),
+            FuzzyEvent::Lost
+            | FuzzyEvent::PartitionStart(_, _)
+            | FuzzyEvent::PartitionRecovery(_, _, _) => None,
+        }
+    }
+
+    /// Whether this event variant can ever result in a `delivery` row. Used
+    /// to sanity-check a recorded run: `Lost`/`PartitionStart`/`PartitionRecovery`
+    /// never send, so a recorded action of one of those variants that somehow
+    /// has delivery rows points at a corrupted or hand-edited recording.
+    pub fn can_deliver(&self) -> bool {
+        !matches!(
+            self,
+            FuzzyEvent::Lost | FuzzyEvent::PartitionStart(_, _) | FuzzyEvent::PartitionRecovery(_, _, _)
+        )
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use scupt_util::error_type::ET;
+use scupt_util::res::Res;
+use tokio::sync::broadcast;
+
+use crate::fuzzy_event::FuzzyEvent;
+
+/// Number of recent events a late subscriber replays before switching to live delivery.
+const HISTORY_CAPACITY: usize = 60;
+
+/// Bounded so a slow consumer lags and drops the oldest events instead of
+/// growing memory or stalling the scheduler that produces them.
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum FuzzyTopic {
+    Message,
+    Partition,
+    Crash,
+    Delivery,
+}
+
+#[derive(Clone, Debug)]
+pub struct FuzzyEventRecord {
+    pub id: u64,
+    pub event: FuzzyEvent,
+}
+
+struct TopicChannel {
+    sender: broadcast::Sender<FuzzyEventRecord>,
+    history: Mutex<VecDeque<FuzzyEventRecord>>,
+}
+
+impl TopicChannel {
+    fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            history: Mutex::new(VecDeque::with_capacity(HISTORY_CAPACITY)),
+        }
+    }
+
+    /// Holds `history`'s lock across both the history push and the broadcast
+    /// send, so a `subscribe()` that also takes the lock for its whole
+    /// critical section can never observe this record in both its history
+    /// snapshot and its live channel.
+    fn notify(&self, record: FuzzyEventRecord) {
+        let mut history = self.history.lock().unwrap();
+        if history.len() == HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        history.push_back(record.clone());
+        // No subscribers is not an error; drop-oldest semantics mean a slow
+        // subscriber can lag but never block or unbound this send.
+        let _ = self.sender.send(record);
+    }
+
+    /// Subscribes under the same `history` lock `notify()` holds, so the
+    /// returned snapshot and the new live receiver split the event stream
+    /// exactly once instead of racing for a given event.
+    fn subscribe(&self) -> (broadcast::Receiver<FuzzyEventRecord>, Vec<FuzzyEventRecord>) {
+        let history = self.history.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let snapshot = history.iter().cloned().collect();
+        (receiver, snapshot)
+    }
+}
+
+/// A small pub/sub event bus so an operator can watch fault decisions live
+/// instead of only inspecting the `action`/`delivery` tables after a run.
+pub struct FuzzyMonitor {
+    message: TopicChannel,
+    partition: TopicChannel,
+    crash: TopicChannel,
+    delivery: TopicChannel,
+}
+
+impl FuzzyMonitor {
+    pub fn new() -> Self {
+        Self {
+            message: TopicChannel::new(),
+            partition: TopicChannel::new(),
+            crash: TopicChannel::new(),
+            delivery: TopicChannel::new(),
+        }
+    }
+
+    fn channel(&self, topic: FuzzyTopic) -> &TopicChannel {
+        match topic {
+            FuzzyTopic::Message => &self.message,
+            FuzzyTopic::Partition => &self.partition,
+            FuzzyTopic::Crash => &self.crash,
+            FuzzyTopic::Delivery => &self.delivery,
+        }
+    }
+
+    pub fn notify(&self, topic: FuzzyTopic, id: u64, event: FuzzyEvent) {
+        self.channel(topic).notify(FuzzyEventRecord { id, event });
+    }
+
+    pub fn subscribe(&self, topic: FuzzyTopic) -> FuzzyEventReceiver {
+        let (receiver, history) = self.channel(topic).subscribe();
+        FuzzyEventReceiver { receiver, history }
+    }
+}
+
+impl Default for FuzzyMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Replays buffered history (oldest first) before switching to live events.
+pub struct FuzzyEventReceiver {
+    receiver: broadcast::Receiver<FuzzyEventRecord>,
+    history: Vec<FuzzyEventRecord>,
+}
+
+impl FuzzyEventReceiver {
+    /// A subscriber that falls behind the channel's `CHANNEL_CAPACITY` gets
+    /// `Lagged`, not `Closed` — that's the expected cost of "drop-oldest so a
+    /// slow subscriber can never block", not a reason to end the
+    /// subscription. Skip past the gap and keep reading; only a genuinely
+    /// closed topic (the monitor itself dropped) ends the stream.
+    pub async fn recv(&mut self) -> Res<FuzzyEventRecord> {
+        if !self.history.is_empty() {
+            return Ok(self.history.remove(0));
+        }
+        loop {
+            match self.receiver.recv().await {
+                Ok(record) => return Ok(record),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Err(ET::EOF),
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use rusqlite::Connection;
+use scupt_util::error_type::ET;
+use scupt_util::res::Res;
+use scupt_util::res_of::res_sqlite;
+
+use crate::fuzzy_event::FuzzyEvent;
+
+/// Reads back the `action` table of a recorded run, ordered the same way it
+/// was originally scheduled, joined with `delivery` to count how many times
+/// each action actually sent, so the exact sequence of delays, duplicates,
+/// partitions and drops can be fed straight into the scheduler again. The
+/// join doesn't change the replayed order (`action.id` already reflects
+/// schedule order) but it does let us catch a corrupted recording: an event
+/// variant that can never deliver (`Lost`, `PartitionStart`,
+/// `PartitionRecovery`) but that has delivery rows anyway.
+pub fn load_recorded_events(path_store: &str) -> Res<Vec<(u64, FuzzyEvent)>> {
+    let conn = Connection::open(path_store).unwrap();
+    let mut stmt = res_sqlite(conn.prepare(
+        r#"select a.id, a.event, count(d.id)
+               from action a left join delivery d on d.action_id = a.id
+               group by a.id
+               order by a.id asc"#,
+    ))?;
+    let rows = res_sqlite(stmt.query_map((), |row| {
+        let id: u64 = row.get(0)?;
+        let event_s: String = row.get(1)?;
+        let delivered_count: u64 = row.get(2)?;
+        Ok((id, event_s, delivered_count))
+    }))?;
+    let mut events = vec![];
+    for row in rows {
+        let (id, event_s, delivered_count) = res_sqlite(row)?;
+        let event: FuzzyEvent = serde_json::from_str(&event_s).unwrap();
+        if !event.can_deliver() && delivered_count > 0 {
+            return Err(ET::FatalError(format!(
+                "recorded action {id} ({event:?}) has {delivered_count} delivery row(s) but its event variant never sends"
+            )));
+        }
+        events.push((id, event));
+    }
+    Ok(events)
+}
@@ -0,0 +1,174 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Mutex;
+
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use scupt_util::message::Message;
+use scupt_util::node_id::NID;
+use tokio::sync::Notify;
+use tokio::time::{sleep_until, Instant};
+
+use crate::fuzzy_event::FuzzyEvent;
+
+/// A single effect a `FuzzyEvent` expands into, to be dispatched once its
+/// deadline is reached. `Send` carries the `FuzzyEvent` it was expanded from
+/// alongside the message, so a delivery can be reported on the `Delivery`
+/// monitor topic with its real provenance (`Delay`, `Duplicate`, `Restart`,
+/// `Mutate`, ...) instead of a fabricated one.
+#[derive(Clone, Debug)]
+pub enum ScheduledAction {
+    Send(u64, Message<String>, FuzzyEvent),
+    Crash(u64, Message<String>),
+    Lost(u64),
+    PartitionStart(u64, Vec<NID>, Vec<NID>),
+    PartitionEnd(u64, Vec<NID>, Vec<NID>),
+}
+
+struct HeapEntry {
+    deadline: Instant,
+    seq: u64,
+    action: ScheduledAction,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline && self.seq == other.seq
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.deadline, self.seq).cmp(&(other.deadline, other.seq))
+    }
+}
+
+/// Timer-driven, deadline-ordered scheduler. Replaces one spawned task per
+/// event with a single heap of pending deadlines, bounding task count to
+/// O(pending events) instead of O(events ever seen).
+pub struct FuzzyScheduler {
+    heap: Mutex<BinaryHeap<Reverse<HeapEntry>>>,
+    seq: AtomicU64,
+    wake: Notify,
+}
+
+impl FuzzyScheduler {
+    pub fn new() -> Self {
+        Self {
+            heap: Mutex::new(BinaryHeap::new()),
+            seq: AtomicU64::new(0),
+            wake: Notify::new(),
+        }
+    }
+
+    /// Schedules `action` to dispatch after `delay` and wakes the worker
+    /// loop if this is now the earliest pending deadline.
+    pub fn push(&self, delay: std::time::Duration, action: ScheduledAction) {
+        let deadline = Instant::now() + delay;
+        let seq = self.seq.fetch_add(1, AtomicOrdering::SeqCst);
+        let is_earliest = {
+            let mut heap = self.heap.lock().unwrap();
+            let earliest_before = heap.peek().map(|Reverse(e)| e.deadline);
+            heap.push(Reverse(HeapEntry { deadline, seq, action }));
+            earliest_before.map(|d| deadline < d).unwrap_or(true)
+        };
+        if is_earliest {
+            self.wake.notify_one();
+        }
+    }
+
+    fn peek_deadline(&self) -> Option<Instant> {
+        self.heap.lock().unwrap().peek().map(|Reverse(e)| e.deadline)
+    }
+
+    fn pop_due(&self, now: Instant) -> Vec<ScheduledAction> {
+        let mut heap = self.heap.lock().unwrap();
+        let mut due = vec![];
+        while let Some(Reverse(entry)) = heap.peek() {
+            if entry.deadline > now {
+                break;
+            }
+            let Reverse(entry) = heap.pop().unwrap();
+            due.push(entry.action);
+        }
+        due
+    }
+
+    /// Runs forever, dispatching due actions through `dispatch` with at most
+    /// `max_inflight` concurrent dispatches so one blocked peer can't stall
+    /// the rest.
+    pub async fn run<F, Fut>(&self, max_inflight: usize, dispatch: F)
+    where
+        F: Fn(ScheduledAction) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        let mut inflight = FuturesUnordered::new();
+        loop {
+            let next_deadline = self.peek_deadline();
+            tokio::select! {
+                _ = sleep_until_or_pending(next_deadline) => {}
+                _ = self.wake.notified() => {}
+                _ = inflight.next(), if !inflight.is_empty() => {}
+            }
+            let due = self.pop_due(Instant::now());
+            for action in due {
+                if inflight.len() >= max_inflight {
+                    inflight.next().await;
+                }
+                inflight.push(dispatch(action));
+            }
+        }
+    }
+}
+
+impl Default for FuzzyScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn sleep_until_or_pending(deadline: Option<Instant>) {
+    match deadline {
+        Some(d) => sleep_until(d).await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Pushes deadlines out of insertion order and checks `run` dispatches
+    /// them earliest-first with a concurrency cap of 2.
+    #[tokio::test]
+    async fn dispatches_in_deadline_order_under_concurrency_cap() {
+        let scheduler = Arc::new(FuzzyScheduler::new());
+        scheduler.push(std::time::Duration::from_millis(30), ScheduledAction::Lost(2));
+        scheduler.push(std::time::Duration::from_millis(10), ScheduledAction::Lost(0));
+        scheduler.push(std::time::Duration::from_millis(20), ScheduledAction::Lost(1));
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let dispatched = order.clone();
+        let run = scheduler.run(2, |action| {
+            let order = dispatched.clone();
+            async move {
+                if let ScheduledAction::Lost(id) = action {
+                    order.lock().unwrap().push(id);
+                }
+            }
+        });
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(200), run).await;
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+    }
+}
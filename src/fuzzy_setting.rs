@@ -0,0 +1,20 @@
+#[derive(Clone, Debug)]
+pub struct FuzzySetting {
+    pub max_delay_ms: u64,
+    pub max_duplicate: usize,
+    pub partition_percent: u8,
+    /// Caps how many dispatches the scheduler runs concurrently, so one
+    /// blocked peer can't stall delivery of the rest.
+    pub max_inflight_sends: usize,
+}
+
+impl Default for FuzzySetting {
+    fn default() -> Self {
+        Self {
+            max_delay_ms: 1000,
+            max_duplicate: 3,
+            partition_percent: 5,
+            max_inflight_sends: 64,
+        }
+    }
+}
@@ -0,0 +1,10 @@
+pub mod event_gen;
+pub mod fuzzy_admin;
+pub mod fuzzy_admin_server;
+pub mod fuzzy_command;
+pub mod fuzzy_driver;
+pub mod fuzzy_event;
+pub mod fuzzy_monitor;
+pub mod fuzzy_replay;
+pub mod fuzzy_scheduler;
+pub mod fuzzy_setting;